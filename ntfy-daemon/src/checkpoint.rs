@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+/// Persists the `since` watermark for a topic so a supervised listener can resume a subscription
+/// across a process restart instead of re-reading from `since=0` or losing its position.
+///
+/// Combined with ntfy's server-side message cache this gives at-least-once delivery across
+/// restarts. Because `since` is advanced to `min_msg.time.max(since)`, a consumer may still see
+/// one duplicate message at the restart boundary.
+#[async_trait]
+pub trait SinceStore: Send + Sync {
+    async fn load(&self, topic: &str) -> anyhow::Result<Option<u64>>;
+    async fn store(&self, topic: &str, since: u64) -> anyhow::Result<()>;
+}
+
+/// In-memory [`SinceStore`]. Checkpoints don't survive a process restart; useful for tests or
+/// listeners that don't need durability.
+#[derive(Default)]
+pub struct MemorySinceStore {
+    since: Mutex<HashMap<String, u64>>,
+}
+
+impl MemorySinceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SinceStore for MemorySinceStore {
+    async fn load(&self, topic: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self.since.lock().unwrap().get(topic).copied())
+    }
+
+    async fn store(&self, topic: &str, since: u64) -> anyhow::Result<()> {
+        self.since.lock().unwrap().insert(topic.to_string(), since);
+        Ok(())
+    }
+}
+
+/// File-backed [`SinceStore`]: keeps a `{ topic: since }` JSON object at `path`, rewritten in
+/// full on every `store`.
+pub struct FileSinceStore {
+    path: PathBuf,
+    cache: Mutex<HashMap<String, u64>>,
+}
+
+impl FileSinceStore {
+    pub fn new(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let cache = if path.exists() {
+            let data = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&data)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path,
+            cache: Mutex::new(cache),
+        })
+    }
+}
+
+#[async_trait]
+impl SinceStore for FileSinceStore {
+    async fn load(&self, topic: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self.cache.lock().unwrap().get(topic).copied())
+    }
+
+    async fn store(&self, topic: &str, since: u64) -> anyhow::Result<()> {
+        let data = {
+            let mut cache = self.cache.lock().unwrap();
+            cache.insert(topic.to_string(), since);
+            serde_json::to_string_pretty(&*cache)?
+        };
+
+        // Write to a temp file in the same directory and rename over the checkpoint, so a crash
+        // mid-write can't leave a truncated file that fails to parse on the next restart.
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_since_store_round_trips() {
+        let store = MemorySinceStore::new();
+        assert_eq!(store.load("topic").await.unwrap(), None);
+
+        store.store("topic", 42).await.unwrap();
+        assert_eq!(store.load("topic").await.unwrap(), Some(42));
+    }
+
+    #[tokio::test]
+    async fn file_since_store_round_trips_and_persists_across_instances() {
+        let path = std::env::temp_dir().join(format!(
+            "ntfy-daemon-since-store-test-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let store = FileSinceStore::new(&path).unwrap();
+        assert_eq!(store.load("topic").await.unwrap(), None);
+
+        store.store("topic", 7).await.unwrap();
+        assert_eq!(store.load("topic").await.unwrap(), Some(7));
+
+        let reloaded = FileSinceStore::new(&path).unwrap();
+        assert_eq!(reloaded.load("topic").await.unwrap(), Some(7));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
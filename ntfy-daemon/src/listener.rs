@@ -7,9 +7,11 @@ use tokio::io::AsyncBufReadExt;
 use tokio::task::{self, spawn_local, LocalSet};
 use tokio::{
     select,
-    sync::{mpsc, oneshot},
+    sync::{mpsc, oneshot, watch},
 };
 use tokio_stream::wrappers::LinesStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
 use tracing::{debug, error, info, warn, Instrument, Span};
 
 use crate::credentials::Credentials;
@@ -43,6 +45,16 @@ pub enum ListenerEvent {
     ConnectionStateChanged(ConnectionState),
 }
 
+/// Which wire protocol a [`ListenerActor`] uses to read the ntfy subscription stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// Read the `/<topic>/json` chunked ndjson stream (the original behavior).
+    #[default]
+    Chunked,
+    /// Read the `/<topic>/ws` WebSocket endpoint, one `ServerEvent` per text frame.
+    WebSocket,
+}
+
 #[derive(Clone)]
 pub struct ListenerConfig {
     pub(crate) http_client: HttpClient,
@@ -50,6 +62,55 @@ pub struct ListenerConfig {
     pub(crate) endpoint: String,
     pub(crate) topic: String,
     pub(crate) since: u64,
+    pub(crate) transport: Transport,
+    /// When set, authenticate via the `?auth=` query parameter instead of an `Authorization`
+    /// header. Needed for EventSource-style clients that can't set custom headers.
+    pub(crate) auth_via_query: bool,
+    /// When set, `since` is loaded from here when the listener starts and persisted after
+    /// every forwarded message, so a process restart resumes instead of re-reading from
+    /// `since=0` or losing position.
+    pub(crate) since_store: Option<Arc<dyn crate::checkpoint::SinceStore>>,
+    /// How long to wait for an open/message/keepalive event before treating the connection as
+    /// dead and triggering a reconnect. ntfy emits a keepalive roughly every 45s, so this should
+    /// comfortably exceed that.
+    pub(crate) keepalive_timeout: Duration,
+    /// Run a bounded one-shot fetch (`?poll=1`): drain the server's cached messages for `topic`
+    /// and return instead of entering the infinite supervised reconnect loop.
+    pub(crate) poll: bool,
+    /// Server-side filters (`priority`, `tags`, `title`, `message`, `id`) so the server drops
+    /// non-matching messages before they ever reach `recv_and_forward_loop`.
+    pub(crate) filters: MessageFilters,
+}
+
+/// Server-side message filters supported by ntfy's subscribe API.
+#[derive(Clone, Debug, Default)]
+pub struct MessageFilters {
+    pub priority: Option<u8>,
+    pub tags: Vec<String>,
+    pub title: Option<String>,
+    pub message: Option<String>,
+    pub id: Option<String>,
+}
+
+impl MessageFilters {
+    fn append_to(&self, url: &mut url::Url) {
+        let mut pairs = url.query_pairs_mut();
+        if let Some(priority) = self.priority {
+            pairs.append_pair("priority", &priority.to_string());
+        }
+        if !self.tags.is_empty() {
+            pairs.append_pair("tags", &self.tags.join(","));
+        }
+        if let Some(title) = &self.title {
+            pairs.append_pair("title", title);
+        }
+        if let Some(message) = &self.message {
+            pairs.append_pair("message", message);
+        }
+        if let Some(id) = &self.id {
+            pairs.append_pair("id", id);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -59,33 +120,141 @@ pub enum ListenerCommand {
     GetState(oneshot::Sender<ConnectionState>),
 }
 
-fn topic_request(
+/// How a request authenticates against the ntfy server.
+#[derive(Clone, Debug)]
+pub(crate) enum AuthMethod {
+    /// `Authorization: Basic <base64(username:password)>`.
+    Basic { username: String, password: String },
+    /// `Authorization: Bearer tk_...`, the access-token form ntfy recommends over Basic auth.
+    Bearer(String),
+}
+
+impl AuthMethod {
+    fn header_value(&self) -> String {
+        use base64::Engine;
+        match self {
+            AuthMethod::Basic { username, password } => {
+                let raw = format!("{username}:{password}");
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(raw)
+                )
+            }
+            AuthMethod::Bearer(token) => format!("Bearer {token}"),
+        }
+    }
+}
+
+pub(crate) fn topic_request(
     client: &HttpClient,
     endpoint: &str,
     topic: &str,
     since: u64,
-    username: Option<&str>,
-    password: Option<&str>,
+    auth: Option<&AuthMethod>,
+    auth_via_query: bool,
+    poll: bool,
+    filters: &MessageFilters,
 ) -> anyhow::Result<reqwest::Request> {
-    let url = models::Subscription::build_url(endpoint, topic, since)?;
+    use base64::Engine;
+
+    let mut url = models::Subscription::build_url(endpoint, topic, since)?;
+
+    if poll {
+        url.query_pairs_mut().append_pair("poll", "1");
+    }
+    filters.append_to(&mut url);
+
+    if auth_via_query {
+        if let Some(auth) = auth {
+            // ntfy decodes `?auth=` with raw URL-safe, no-padding base64 - a STANDARD-encoded
+            // value containing `+`, `/`, or `=` fails to decode server-side.
+            let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(auth.header_value());
+            url.query_pairs_mut().append_pair("auth", &encoded);
+        }
+    }
+
     let mut req = client
         .get(url.as_str())
         .header("Content-Type", "application/x-ndjson")
         .header("Transfer-Encoding", "chunked");
-    if let Some(username) = username {
-        req = req.basic_auth(username, password);
+
+    if !auth_via_query {
+        match auth {
+            Some(AuthMethod::Basic { username, password }) => {
+                req = req.basic_auth(username, Some(password));
+            }
+            Some(AuthMethod::Bearer(token)) => {
+                req = req.bearer_auth(token);
+            }
+            None => {}
+        }
     }
 
     Ok(req.build()?)
 }
 
-async fn response_lines(
+pub(crate) async fn response_lines(
     res: impl tokio::io::AsyncBufRead,
 ) -> Result<impl futures::Stream<Item = Result<String, std::io::Error>>, reqwest::Error> {
     let lines = LinesStream::new(res.lines());
     Ok(lines)
 }
 
+/// Open the `/<topic>/ws` endpoint and return a stream yielding one decoded text frame per item.
+///
+/// Frames are passed through verbatim; the caller parses each one with
+/// `serde_json::from_str::<ServerEvent>` exactly as it does for ndjson lines, so `since`
+/// bookkeeping and event emission stay identical across transports.
+async fn ws_lines(
+    endpoint: &str,
+    topic: &str,
+    since: u64,
+    auth: Option<&AuthMethod>,
+    auth_via_query: bool,
+    poll: bool,
+    filters: &MessageFilters,
+) -> anyhow::Result<impl futures::Stream<Item = anyhow::Result<String>>> {
+    use base64::Engine;
+
+    // FIXME(chunk0-1): build_ws_url ships with models.rs in this series; that file isn't part
+    // of this snapshot, so this call doesn't resolve here.
+    let mut url = models::Subscription::build_ws_url(endpoint, topic)?;
+    url.query_pairs_mut().append_pair("since", &since.to_string());
+
+    if poll {
+        url.query_pairs_mut().append_pair("poll", "1");
+    }
+    filters.append_to(&mut url);
+
+    if auth_via_query {
+        if let Some(auth) = auth {
+            let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(auth.header_value());
+            url.query_pairs_mut().append_pair("auth", &encoded);
+        }
+    }
+
+    let mut request = url.as_str().into_client_request()?;
+    if !auth_via_query {
+        if let Some(auth) = auth {
+            request
+                .headers_mut()
+                .insert("Authorization", auth.header_value().parse()?);
+        }
+    }
+
+    let (ws_stream, _) = tokio_tungstenite::connect_async(request).await?;
+    let lines = ws_stream.filter_map(|msg| async move {
+        match msg {
+            Ok(WsMessage::Text(text)) => Some(Ok(text)),
+            Ok(_) => None,
+            Err(e) => Some(Err(anyhow::Error::from(e))),
+        }
+    });
+    Ok(lines)
+}
+
 #[derive(Clone, Debug)]
 pub enum ConnectionState {
     Unitialized,
@@ -102,6 +271,7 @@ pub struct ListenerActor {
     pub commands_rx: Option<mpsc::Receiver<ListenerCommand>>,
     pub config: ListenerConfig,
     pub state: ConnectionState,
+    pub state_tx: watch::Sender<ConnectionState>,
 }
 
 impl ListenerActor {
@@ -147,6 +317,8 @@ impl ListenerActor {
 
     async fn set_state(&mut self, state: ConnectionState) {
         self.state = state.clone();
+        // ignore send errors - it's fine if nobody's watching
+        let _ = self.state_tx.send(state.clone());
         self.event_tx
             .send(ListenerEvent::ConnectionStateChanged(state))
             .await
@@ -155,6 +327,22 @@ impl ListenerActor {
     async fn run_supervised_loop(&mut self) {
         let span = tracing::info_span!("supervised_loop");
         async {
+            if self.config.poll {
+                // poll mode is a bounded one-shot fetch: drain the cache and return, success or
+                // failure, instead of engaging the infinite exponential-backoff reconnect loop
+                // below.
+                if let Err(e) = self.recv_and_forward_loop().await {
+                    error!(error = ?e, "poll request failed");
+                    self.set_state(ConnectionState::Reconnecting {
+                        retry_count: 0,
+                        delay: Duration::ZERO,
+                        error: Some(Arc::new(e)),
+                    })
+                    .await;
+                }
+                return;
+            }
+
             let retrier = || {
                 crate::retry::WaitExponentialRandom::builder()
                     .min(Duration::from_secs(1))
@@ -198,31 +386,82 @@ impl ListenerActor {
         );
         async {
             let creds = self.config.credentials.get(&self.config.endpoint);
-            debug!("creating request");
-            let req = topic_request(
-                &self.config.http_client,
-                &self.config.endpoint,
-                &self.config.topic,
-                self.config.since,
-                creds.as_ref().map(|x| x.username.as_str()),
-                creds.as_ref().map(|x| x.password.as_str()),
-            );
-
-            debug!("executing request");
-            let res = self.config.http_client.execute(req?).await?;
-            let res = res.error_for_status()?;
-            let reader = tokio_util::io::StreamReader::new(
-                res.bytes_stream()
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string())),
-            );
-            let stream = response_lines(reader).await?;
+            // FIXME(chunk0-3): `token` lands on Credential alongside username/password in
+            // credentials.rs; that file isn't part of this snapshot, so this field doesn't
+            // resolve here.
+            let auth = creds.as_ref().map(|c| match &c.token {
+                Some(token) => AuthMethod::Bearer(token.clone()),
+                None => AuthMethod::Basic {
+                    username: c.username.clone(),
+                    password: c.password.clone(),
+                },
+            });
+            debug!(transport = ?self.config.transport, "creating request");
+            let stream: std::pin::Pin<Box<dyn futures::Stream<Item = anyhow::Result<String>>>> =
+                match self.config.transport {
+                    Transport::Chunked => {
+                        let req = topic_request(
+                            &self.config.http_client,
+                            &self.config.endpoint,
+                            &self.config.topic,
+                            self.config.since,
+                            auth.as_ref(),
+                            self.config.auth_via_query,
+                            self.config.poll,
+                            &self.config.filters,
+                        )?;
+
+                        debug!("executing request");
+                        let res = self.config.http_client.execute(req).await?;
+                        let res = res.error_for_status()?;
+                        let reader = tokio_util::io::StreamReader::new(res.bytes_stream().map_err(
+                            |e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()),
+                        ));
+                        let lines = response_lines(reader).await?;
+                        Box::pin(lines.map_err(anyhow::Error::from))
+                    }
+                    Transport::WebSocket => {
+                        let lines = ws_lines(
+                            &self.config.endpoint,
+                            &self.config.topic,
+                            self.config.since,
+                            auth.as_ref(),
+                            self.config.auth_via_query,
+                            self.config.poll,
+                            &self.config.filters,
+                        )
+                        .await?;
+                        Box::pin(lines)
+                    }
+                };
             tokio::pin!(stream);
 
             self.set_state(ConnectionState::Connected).await;
             info!("connection established");
 
             info!(topic = %&self.config.topic, "listening");
-            while let Some(msg) = stream.next().await {
+            loop {
+                // in poll mode the server closes the stream deliberately once the cache is
+                // drained, which can take longer than keepalive_timeout with nothing in between -
+                // don't mistake that for a dead connection.
+                let msg = if self.config.poll {
+                    match stream.next().await {
+                        Some(msg) => msg,
+                        None => break,
+                    }
+                } else {
+                    match tokio::time::timeout(self.config.keepalive_timeout, stream.next()).await
+                    {
+                        Ok(Some(msg)) => msg,
+                        Ok(None) => break,
+                        Err(_) => {
+                            return Err(anyhow::anyhow!(
+                                "no open/message/keepalive received within {:?}, assuming dead connection",
+                                self.config.keepalive_timeout
+                            ))
+                        }
+                    }
+                };
                 let msg = msg?;
 
                 let min_msg = serde_json::from_str::<models::MinMessage>(&msg)
@@ -239,6 +478,14 @@ impl ListenerActor {
                             .send(ListenerEvent::Message(msg))
                             .await
                             .unwrap();
+
+                        if let Some(store) = &self.config.since_store {
+                            if let Err(e) =
+                                store.store(&self.config.topic, self.config.since).await
+                            {
+                                warn!(error = ?e, "failed to persist since checkpoint");
+                            }
+                        }
                     }
                     ServerEvent::KeepAlive { id, .. } => {
                         debug!(id = %id, "received keepalive");
@@ -262,23 +509,38 @@ pub struct ListenerHandle {
     pub events: async_channel::Receiver<ListenerEvent>,
     pub config: ListenerConfig,
     pub commands: mpsc::Sender<ListenerCommand>,
+    /// Cheaply readable, pollable connection state. Prefer this over [`ListenerHandle::state`]
+    /// - it doesn't round-trip through the command channel, so it can't be starved by a full
+    /// `commands` queue, and `changed()` can be awaited passively.
+    pub state_rx: watch::Receiver<ConnectionState>,
 }
 
 impl ListenerHandle {
     pub fn new(config: ListenerConfig) -> ListenerHandle {
         let (event_tx, event_rx) = async_channel::bounded(64);
         let (commands_tx, commands_rx) = mpsc::channel(1);
+        let (state_tx, state_rx) = watch::channel(ConnectionState::Unitialized);
 
         let config_clone = config.clone();
 
         // use a new local set to isolate panics
         let local_set = LocalSet::new();
         local_set.spawn_local(async move {
+            let mut config_clone = config_clone;
+            if let Some(store) = &config_clone.since_store {
+                match store.load(&config_clone.topic).await {
+                    Ok(Some(since)) => config_clone.since = since,
+                    Ok(None) => {}
+                    Err(e) => warn!(error = ?e, "failed to load since checkpoint"),
+                }
+            }
+
             let this = ListenerActor {
                 event_tx,
                 commands_rx: Some(commands_rx),
                 config: config_clone,
                 state: ConnectionState::Unitialized,
+                state_tx,
             };
 
             this.run_loop().await;
@@ -289,6 +551,7 @@ impl ListenerHandle {
             events: event_rx,
             config,
             commands: commands_tx,
+            state_rx,
         }
     }
 
@@ -334,6 +597,12 @@ mod tests {
                     endpoint: "http://localhost".to_string(),
                     topic: "test".to_string(),
                     since: 0,
+                    transport: Transport::Chunked,
+                    auth_via_query: false,
+                    since_store: None,
+                    keepalive_timeout: Duration::from_secs(90),
+                    poll: false,
+                    filters: MessageFilters::default(),
                 };
 
                 let listener = ListenerHandle::new(config.clone());
@@ -373,6 +642,12 @@ mod tests {
                     endpoint: "http://localhost".to_string(),
                     topic: "test".to_string(),
                     since: 0,
+                    transport: Transport::Chunked,
+                    auth_via_query: false,
+                    since_store: None,
+                    keepalive_timeout: Duration::from_secs(90),
+                    poll: false,
+                    filters: MessageFilters::default(),
                 };
 
                 let listener = ListenerHandle::new(config.clone());
@@ -390,4 +665,84 @@ mod tests {
             });
         local_set.await;
     }
+
+    #[test]
+    fn message_filters_append_to_builds_expected_query() {
+        let mut url = Subscription::build_url("http://localhost", "test", 0).unwrap();
+        let filters = MessageFilters {
+            priority: Some(4),
+            tags: vec!["foo".to_string(), "bar".to_string()],
+            title: Some("hello".to_string()),
+            message: None,
+            id: None,
+        };
+
+        filters.append_to(&mut url);
+
+        let pairs: Vec<_> = url.query_pairs().into_owned().collect();
+        assert!(pairs.contains(&("priority".to_string(), "4".to_string())));
+        assert!(pairs.contains(&("tags".to_string(), "foo,bar".to_string())));
+        assert!(pairs.contains(&("title".to_string(), "hello".to_string())));
+        assert!(!pairs.iter().any(|(k, _)| k == "message"));
+        assert!(!pairs.iter().any(|(k, _)| k == "id"));
+    }
+
+    #[test]
+    fn topic_request_appends_poll_query_param() {
+        let http_client = HttpClient::new_nullable(NullableClient::builder().build());
+
+        let req = topic_request(
+            &http_client,
+            "http://localhost",
+            "test",
+            0,
+            None,
+            false,
+            true,
+            &MessageFilters::default(),
+        )
+        .unwrap();
+
+        let pairs: Vec<_> = req.url().query_pairs().into_owned().collect();
+        assert!(pairs.contains(&("poll".to_string(), "1".to_string())));
+    }
+
+    #[test]
+    fn topic_request_encodes_query_auth_as_url_safe_no_pad_base64() {
+        use base64::Engine;
+
+        let http_client = HttpClient::new_nullable(NullableClient::builder().build());
+        let auth = AuthMethod::Bearer("tk_test_token".to_string());
+
+        let req = topic_request(
+            &http_client,
+            "http://localhost",
+            "test",
+            0,
+            Some(&auth),
+            true,
+            false,
+            &MessageFilters::default(),
+        )
+        .unwrap();
+
+        let encoded = req
+            .url()
+            .query_pairs()
+            .find(|(k, _)| k == "auth")
+            .map(|(_, v)| v.into_owned())
+            .unwrap();
+
+        // Standard base64's `+`, `/`, `=` aren't valid in a query param without further escaping,
+        // and ntfy decodes `?auth=` as raw URL-safe, no-padding base64 specifically to avoid them.
+        assert!(!encoded.contains('+') && !encoded.contains('/') && !encoded.contains('='));
+
+        let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(&encoded)
+            .unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), "Bearer tk_test_token");
+
+        // query auth replaces the header, it doesn't duplicate it
+        assert!(req.headers().get("Authorization").is_none());
+    }
 }
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{StreamExt, TryStreamExt};
+use tokio::sync::mpsc;
+use tokio::task::{spawn_local, LocalSet};
+use tracing::{debug, error, info, Instrument};
+
+use crate::credentials::Credentials;
+use crate::http_client::HttpClient;
+use crate::listener::{
+    response_lines, topic_request, AuthMethod, ConnectionState, ListenerEvent, MessageFilters,
+    ServerEvent,
+};
+use crate::{models, Error};
+
+/// Configuration for a [`SubscriptionManager`], which multiplexes several ntfy topics over a
+/// single HTTP connection instead of opening one [`crate::listener::ListenerHandle`] per topic.
+#[derive(Clone)]
+pub struct SubscriptionManagerConfig {
+    pub(crate) http_client: HttpClient,
+    pub(crate) credentials: Credentials,
+    pub(crate) endpoint: String,
+    pub(crate) since: u64,
+    pub(crate) auth_via_query: bool,
+    pub(crate) keepalive_timeout: Duration,
+}
+
+type SubId = u64;
+
+struct SubscriptionTable {
+    next_sub_id: SubId,
+    // topic -> (sub_id, sender), mirrors the `Chats` subscription table: insert on subscribe,
+    // remove on drop.
+    subs: HashMap<String, Vec<(SubId, async_channel::Sender<ListenerEvent>)>>,
+}
+
+impl SubscriptionTable {
+    fn new() -> Self {
+        Self {
+            next_sub_id: 0,
+            subs: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, topic: &str, tx: async_channel::Sender<ListenerEvent>) -> SubId {
+        let sub_id = self.next_sub_id;
+        self.next_sub_id += 1;
+        self.subs
+            .entry(topic.to_string())
+            .or_default()
+            .push((sub_id, tx));
+        sub_id
+    }
+
+    fn remove(&mut self, topic: &str, sub_id: SubId) {
+        if let Some(subs) = self.subs.get_mut(topic) {
+            subs.retain(|(id, _)| *id != sub_id);
+            if subs.is_empty() {
+                self.subs.remove(topic);
+            }
+        }
+    }
+
+    fn topics(&self) -> Vec<String> {
+        self.subs.keys().cloned().collect()
+    }
+
+    fn senders_for(&self, topic: &str) -> Vec<async_channel::Sender<ListenerEvent>> {
+        self.subs
+            .get(topic)
+            .map(|subs| subs.iter().map(|(_, tx)| tx.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A per-topic handle returned by [`SubscriptionManager::subscribe`]. Dropping it removes this
+/// handle's registration from the manager's subscription table; the topic itself leaves the
+/// table once its last handle is dropped.
+pub struct TopicHandle {
+    topic: String,
+    sub_id: SubId,
+    table: Arc<Mutex<SubscriptionTable>>,
+    restart: mpsc::Sender<()>,
+    pub events: async_channel::Receiver<ListenerEvent>,
+}
+
+impl Drop for TopicHandle {
+    fn drop(&mut self) {
+        self.table.lock().unwrap().remove(&self.topic, self.sub_id);
+        // wake the actor so it drops this topic from the live multiplexed subscription instead
+        // of continuing to request it until some unrelated reconnect rebuilds the topic set
+        let _ = self.restart.try_send(());
+    }
+}
+
+/// Opens a single HTTP subscription covering every topic currently subscribed to, and
+/// demultiplexes incoming `ServerEvent`s to the per-topic channels registered via
+/// [`SubscriptionManager::subscribe`].
+///
+/// This avoids one TCP connection per topic for applications watching many topics.
+#[derive(Clone)]
+pub struct SubscriptionManager {
+    config: SubscriptionManagerConfig,
+    table: Arc<Mutex<SubscriptionTable>>,
+    restart: mpsc::Sender<()>,
+    /// Every `Message`/`ConnectionStateChanged` event, across all subscribed topics, in
+    /// arrival order. `ListenerEvent::Message` carries its own `topic` field for callers that
+    /// need to tell topics apart.
+    pub combined_events: async_channel::Receiver<ListenerEvent>,
+    combined_tx: async_channel::Sender<ListenerEvent>,
+}
+
+impl SubscriptionManager {
+    pub fn new(config: SubscriptionManagerConfig) -> Self {
+        let table = Arc::new(Mutex::new(SubscriptionTable::new()));
+        let (restart_tx, restart_rx) = mpsc::channel(1);
+        let (combined_tx, combined_events) = async_channel::bounded(256);
+
+        let actor_table = table.clone();
+        let actor_config = config.clone();
+        let actor_combined_tx = combined_tx.clone();
+        let local_set = LocalSet::new();
+        local_set.spawn_local(async move {
+            run_loop(actor_config, actor_table, restart_rx, actor_combined_tx).await;
+        });
+        spawn_local(local_set);
+
+        Self {
+            config,
+            table,
+            restart: restart_tx,
+            combined_events,
+            combined_tx,
+        }
+    }
+
+    /// Subscribe to `topic`, returning a handle whose `events` receiver yields only the
+    /// messages for that topic. Dropping the handle unsubscribes.
+    pub fn subscribe(&self, topic: &str) -> TopicHandle {
+        let (tx, rx) = async_channel::bounded(64);
+        let sub_id = self.table.lock().unwrap().insert(topic, tx);
+        // wake the actor so it reopens the connection covering the new topic set
+        let _ = self.restart.try_send(());
+        TopicHandle {
+            topic: topic.to_string(),
+            sub_id,
+            table: self.table.clone(),
+            restart: self.restart.clone(),
+            events: rx,
+        }
+    }
+}
+
+async fn run_loop(
+    config: SubscriptionManagerConfig,
+    table: Arc<Mutex<SubscriptionTable>>,
+    mut restart_rx: mpsc::Receiver<()>,
+    combined_tx: async_channel::Sender<ListenerEvent>,
+) {
+    let span = tracing::info_span!("subscription_manager_loop");
+    async {
+        let mut since = config.since;
+        loop {
+            let retrier = || {
+                crate::retry::WaitExponentialRandom::builder()
+                    .min(Duration::from_secs(1))
+                    .max(Duration::from_secs(5 * 60))
+                    .build()
+            };
+            let mut retry = retrier();
+            loop {
+                let topics = table.lock().unwrap().topics();
+                if topics.is_empty() {
+                    // nothing to subscribe to yet; wait for the first subscribe() call
+                    restart_rx.recv().await;
+                    continue;
+                }
+
+                tokio::select! {
+                    res = recv_and_forward_loop(&config, &table, &topics, &mut since, &combined_tx) => {
+                        match res {
+                            Ok(()) => break,
+                            Err(e) => {
+                                error!(error = ?e, "subscription manager connection error");
+                                try_send_combined(
+                                    &combined_tx,
+                                    ListenerEvent::ConnectionStateChanged(
+                                        ConnectionState::Reconnecting {
+                                            retry_count: retry.count(),
+                                            delay: retry.next_delay(),
+                                            error: Some(Arc::new(e)),
+                                        },
+                                    ),
+                                );
+                                retry.wait().await;
+                            }
+                        }
+                    }
+                    _ = restart_rx.recv() => {
+                        info!("topic set changed, reconnecting");
+                        retry = retrier();
+                    }
+                }
+            }
+        }
+    }
+    .instrument(span)
+    .await;
+}
+
+async fn recv_and_forward_loop(
+    config: &SubscriptionManagerConfig,
+    table: &Arc<Mutex<SubscriptionTable>>,
+    topics: &[String],
+    since: &mut u64,
+    combined_tx: &async_channel::Sender<ListenerEvent>,
+) -> anyhow::Result<()> {
+    let combined_topic = topics.join(",");
+    let creds = config.credentials.get(&config.endpoint);
+    // FIXME(chunk0-3): same `Credential.token` gap as listener.rs's recv_and_forward_loop.
+    let auth = creds.as_ref().map(|c| match &c.token {
+        Some(token) => AuthMethod::Bearer(token.clone()),
+        None => AuthMethod::Basic {
+            username: c.username.clone(),
+            password: c.password.clone(),
+        },
+    });
+    let req = topic_request(
+        &config.http_client,
+        &config.endpoint,
+        &combined_topic,
+        *since,
+        auth.as_ref(),
+        config.auth_via_query,
+        false,
+        &MessageFilters::default(),
+    )?;
+
+    let res = config.http_client.execute(req).await?;
+    let res = res.error_for_status()?;
+    let reader = tokio_util::io::StreamReader::new(
+        res.bytes_stream()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string())),
+    );
+    let stream = response_lines(reader).await?;
+    tokio::pin!(stream);
+
+    info!(topics = %combined_topic, "multiplexed connection established");
+    try_send_combined(
+        combined_tx,
+        ListenerEvent::ConnectionStateChanged(ConnectionState::Connected),
+    );
+
+    loop {
+        // mirrors the per-topic ListenerActor's watchdog: a multiplexed connection that stops
+        // producing open/message/keepalive frames is assumed dead and reconnected.
+        let msg = match tokio::time::timeout(config.keepalive_timeout, stream.next()).await {
+            Ok(Some(msg)) => msg,
+            Ok(None) => break,
+            Err(_) => {
+                return Err(anyhow::anyhow!(
+                    "no open/message/keepalive received within {:?}, assuming dead connection",
+                    config.keepalive_timeout
+                ))
+            }
+        };
+        let msg = msg?;
+
+        let min_msg = serde_json::from_str::<models::MinMessage>(&msg)
+            .map_err(|e| Error::InvalidMinMessage(msg.to_string(), e))?;
+        *since = min_msg.time.max(*since);
+
+        let event: ServerEvent =
+            serde_json::from_str(&msg).map_err(|e| Error::InvalidMessage(msg.to_string(), e))?;
+
+        match event {
+            ServerEvent::Message(msg) => {
+                let topic = msg.topic.clone();
+                let event = ListenerEvent::Message(msg);
+
+                let senders = table.lock().unwrap().senders_for(&topic);
+                if senders.is_empty() {
+                    debug!(topic, "no subscribers for topic, dropping event");
+                }
+                for tx in senders {
+                    // try_send rather than send().await: a single slow/stalled subscriber must
+                    // not be able to head-of-line-block delivery to every other topic sharing
+                    // this connection.
+                    match tx.try_send(event.clone()) {
+                        Ok(()) => {}
+                        Err(async_channel::TrySendError::Full(_)) => {
+                            debug!(topic, "subscriber channel full, dropping event");
+                        }
+                        Err(async_channel::TrySendError::Closed(_)) => {
+                            debug!(topic, "subscriber channel closed, will be dropped");
+                        }
+                    }
+                }
+
+                try_send_combined(combined_tx, event);
+            }
+            ServerEvent::KeepAlive { id, .. } => {
+                debug!(id = %id, "received keepalive");
+            }
+            ServerEvent::Open { id, .. } => {
+                debug!(id = %id, "received open event");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Non-blocking send to `combined_events`: a caller that only ever drains per-topic
+/// `TopicHandle`s (and never reads `combined_events`) must not be able to stall the shared
+/// read loop once the bounded channel fills up.
+fn try_send_combined(combined_tx: &async_channel::Sender<ListenerEvent>, event: ListenerEvent) {
+    if let Err(async_channel::TrySendError::Full(_)) = combined_tx.try_send(event) {
+        debug!("combined event channel full, dropping event");
+    }
+}
+
+// Keepalive-watchdog-firing and WebSocket-transport coverage aren't included here: the former
+// needs a mock HTTP client that can stall a response mid-stream (NullableClient's canned
+// responses resolve immediately, so a watchdog timeout never has anything to race against),
+// and the latter doesn't compile yet (see the FIXME in `ws_lines`).
+#[cfg(test)]
+mod tests {
+    use models::Subscription;
+    use serde_json::json;
+
+    use crate::credentials::Credentials;
+    use crate::http_client::NullableClient;
+
+    use super::*;
+
+    // Unlike the per-topic ListenerActor (which terminates its supervised loop for good on a
+    // clean stream close), `run_loop` here reconnects forever by design - it has to survive
+    // subscribe()/unsubscribe() cycles. `LocalSet::run_until` drives only as long as it takes
+    // the handle below to resolve, instead of `LocalSet::await`, which would hang waiting for
+    // the actor task to finish on its own.
+    #[tokio::test]
+    async fn subscription_manager_demuxes_events_to_the_matching_topic_and_combined_stream() {
+        let local_set = LocalSet::new();
+        let handle = local_set.spawn_local(async {
+            let url = Subscription::build_url("http://localhost", "foo", 0).unwrap();
+            let body = format!(
+                "{}\n{}",
+                json!({"id": "aaa", "time": 1, "event": "message", "topic": "foo", "message": "hi foo"}),
+                json!({"id": "bbb", "time": 2, "event": "message", "topic": "bar", "message": "hi bar"}),
+            );
+            let http_client = HttpClient::new_nullable(
+                NullableClient::builder()
+                    .text_response(url, 200, body)
+                    .build(),
+            );
+            let credentials = Credentials::new_nullable(vec![]).await.unwrap();
+
+            let manager = SubscriptionManager::new(SubscriptionManagerConfig {
+                http_client,
+                credentials,
+                endpoint: "http://localhost".to_string(),
+                since: 0,
+                auth_via_query: false,
+                keepalive_timeout: Duration::from_secs(90),
+            });
+
+            // only "foo" is subscribed, so the combined_topic this opens is deterministic even
+            // though the subscription table is keyed by an unordered HashMap
+            let foo = manager.subscribe("foo");
+
+            let foo_items: Vec<_> = foo.events.take(1).collect().await;
+            assert!(matches!(
+                &foo_items[..],
+                [ListenerEvent::Message(msg)] if msg.topic == "foo"
+            ));
+
+            let combined_items: Vec<_> = manager.combined_events.clone().take(3).collect().await;
+            assert!(matches!(
+                &combined_items[..],
+                [
+                    ListenerEvent::ConnectionStateChanged(ConnectionState::Connected),
+                    ListenerEvent::Message(first),
+                    ListenerEvent::Message(second),
+                ] if first.topic == "foo" && second.topic == "bar"
+            ));
+        });
+        local_set.run_until(handle).await.unwrap();
+    }
+}